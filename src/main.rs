@@ -1,16 +1,128 @@
 extern crate charming;
 extern crate chrono;
+extern crate image;
 extern crate polars;
+extern crate printpdf;
 
 use charming::{
     component::{Axis, Grid, Legend, Title},
-    element::{AreaStyle, ItemStyle, LineStyle},
+    element::{
+        AreaStyle, AxisType, ItemStyle, LineStyle, MarkLine, MarkLineData, MarkLineVariant,
+        MarkPoint, MarkPointData, Symbol,
+    },
     series::Line,
     Chart, ImageFormat, ImageRenderer,
 };
+use chrono::NaiveDate;
 use polars::prelude::*;
 use std::error::Error;
 
+/// Factor relating the two glucose units: mg/dL = mmol/L × 18.0182.
+const MMOL_PER_MGDL: f64 = 18.0182;
+
+/// The unit glucose values are expressed in throughout cleaning, stats and plotting.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum GlucoseUnit {
+    MgDl,
+    MmolL,
+}
+
+impl GlucoseUnit {
+    fn parse(value: &str) -> Result<GlucoseUnit, String> {
+        match value.to_lowercase().as_str() {
+            "mg/dl" | "mgdl" => Ok(GlucoseUnit::MgDl),
+            "mmol/l" | "mmoll" => Ok(GlucoseUnit::MmolL),
+            other => Err(format!("Unknown glucose unit: {other}")),
+        }
+    }
+
+    /// Convert a value stored in mg/dL into this unit.
+    fn from_mgdl(self, value: f64) -> f64 {
+        match self {
+            GlucoseUnit::MgDl => value,
+            GlucoseUnit::MmolL => value / MMOL_PER_MGDL,
+        }
+    }
+
+    /// Convert a value stored in this unit back into mg/dL.
+    fn to_mgdl(self, value: f64) -> f64 {
+        match self {
+            GlucoseUnit::MgDl => value,
+            GlucoseUnit::MmolL => value * MMOL_PER_MGDL,
+        }
+    }
+
+    /// Label used for the y-axis names and the metrics summary. The internal
+    /// glucose column keeps its `(mg/dL)` name in either unit.
+    fn label(self) -> &'static str {
+        match self {
+            GlucoseUnit::MgDl => "mg/dL",
+            GlucoseUnit::MmolL => "mmol/L",
+        }
+    }
+
+    /// Sensible y-axis grid interval for this unit.
+    fn axis_interval(self) -> f64 {
+        match self {
+            GlucoseUnit::MgDl => 25.0,
+            GlucoseUnit::MmolL => 2.0,
+        }
+    }
+}
+
+/// Low/high glucose thresholds bounding the in-target zone, in the active unit.
+#[derive(Clone, Copy)]
+struct Thresholds {
+    low: f64,
+    high: f64,
+}
+
+impl Thresholds {
+    /// Default Dexcom-style range (70/180 mg/dL) expressed in the given unit.
+    fn defaults(unit: GlucoseUnit) -> Thresholds {
+        Thresholds {
+            low: unit.from_mgdl(70.0),
+            high: unit.from_mgdl(180.0),
+        }
+    }
+}
+
+/// Which target band a reading falls into.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum TargetZone {
+    Below,
+    InRange,
+    Above,
+}
+
+impl TargetZone {
+    fn of(value: f64, thresholds: Thresholds) -> TargetZone {
+        if value < thresholds.low {
+            TargetZone::Below
+        } else if value > thresholds.high {
+            TargetZone::Above
+        } else {
+            TargetZone::InRange
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            TargetZone::Below => "Below Target",
+            TargetZone::InRange => "In Target",
+            TargetZone::Above => "Above Target",
+        }
+    }
+
+    fn color(self) -> &'static str {
+        match self {
+            TargetZone::Below => "#d33",
+            TargetZone::InRange => "#4d4",
+            TargetZone::Above => "#dd3",
+        }
+    }
+}
+
 fn read_exported_dexcom_values(file_path: &str) -> PolarsResult<DataFrame> {
     CsvReadOptions::default()
         .with_has_header(true)
@@ -18,7 +130,54 @@ fn read_exported_dexcom_values(file_path: &str) -> PolarsResult<DataFrame> {
         .finish()
 }
 
-fn clean_data(df: DataFrame) -> PolarsResult<DataFrame> {
+/// Read a companion activity export: a CSV with `Timestamp`, `Type` and an
+/// optional `Note` column describing meals, insulin, exercise, etc. XLSX is
+/// not supported yet; reject it explicitly rather than mis-parsing it as CSV.
+fn read_activity_file(file_path: &str) -> PolarsResult<DataFrame> {
+    if file_path.ends_with(".xlsx") || file_path.ends_with(".xls") {
+        return Err(PolarsError::ComputeError(
+            "--activity only supports CSV files; XLSX is not yet supported".into(),
+        ));
+    }
+
+    let df = CsvReadOptions::default()
+        .with_has_header(true)
+        .try_into_reader_with_file_path(Some(file_path.into()))?
+        .finish()?;
+
+    df.lazy()
+        .with_column(col("Timestamp").str().strptime(
+            DataType::Datetime(TimeUnit::Milliseconds, None),
+            StrptimeOptions {
+                format: Some("%Y-%m-%dT%H:%M:%S".to_string()),
+                strict: false,
+                ..Default::default()
+            },
+            lit("raise"),
+        ))
+        .collect()
+}
+
+/// Restrict a frame to the `[since, until)` window (epoch-millisecond bounds)
+/// on its datetime column, so the glucose and activity data share one window.
+fn apply_window(
+    df: DataFrame,
+    timestamp_col: &str,
+    since_ms: Option<i64>,
+    until_ms: Option<i64>,
+) -> PolarsResult<DataFrame> {
+    let mut lazy = df.lazy();
+    let epoch_ms = col(timestamp_col).cast(DataType::Int64);
+    if let Some(since) = since_ms {
+        lazy = lazy.filter(epoch_ms.clone().gt_eq(lit(since)));
+    }
+    if let Some(until) = until_ms {
+        lazy = lazy.filter(epoch_ms.lt(lit(until)));
+    }
+    lazy.collect()
+}
+
+fn clean_data(df: DataFrame, unit: GlucoseUnit) -> PolarsResult<DataFrame> {
     let timestamp_col = "Timestamp (YYYY-MM-DDThh:mm:ss)";
     let glucose_col = "Glucose Value (mg/dL)";
 
@@ -40,6 +199,12 @@ fn clean_data(df: DataFrame) -> PolarsResult<DataFrame> {
                 .otherwise(col(glucose_col))
                 .alias(glucose_col),
         )
+        // Convert the mg/dL readings into the requested unit; the "Low"→30 mg/dL
+        // substitution above is carried along so it becomes ≈1.7 mmol/L.
+        .with_column(match unit {
+            GlucoseUnit::MgDl => col(glucose_col).cast(DataType::Float64),
+            GlucoseUnit::MmolL => col(glucose_col).cast(DataType::Float64) / lit(MMOL_PER_MGDL),
+        })
         .with_column(col(timestamp_col).str().strptime(
             DataType::Datetime(TimeUnit::Milliseconds, None),
             StrptimeOptions {
@@ -54,6 +219,95 @@ fn clean_data(df: DataFrame) -> PolarsResult<DataFrame> {
     return cleaned_df.drop_nulls::<String>(None);
 }
 
+/// Linearly interpolate across runs of missing values on a regular grid of
+/// `interval_minutes`-wide slots, but only where the gap spans fewer than
+/// `max_gap_minutes`; longer gaps are left null so sensor-off periods are not
+/// fabricated. Compared in minutes rather than a pre-truncated slot count, so
+/// a `max_gap` that isn't an exact multiple of `interval` is still honored.
+fn interpolate_short_gaps(values: &[Option<f64>], interval_minutes: i64, max_gap_minutes: i64) -> Vec<Option<f64>> {
+    let mut out = values.to_vec();
+    let mut i = 0;
+    while i < out.len() {
+        if out[i].is_some() {
+            i += 1;
+            continue;
+        }
+        // [start, end) is a run of nulls bounded by `prev` and `next` readings.
+        let start = i;
+        while i < out.len() && out[i].is_none() {
+            i += 1;
+        }
+        let run = i - start;
+        if start > 0 && i < out.len() && (run as i64) * interval_minutes < max_gap_minutes {
+            let prev = out[start - 1].unwrap();
+            let next = out[i].unwrap();
+            for (offset, slot) in out[start..i].iter_mut().enumerate() {
+                let frac = (offset + 1) as f64 / (run + 1) as f64;
+                *slot = Some(prev + frac * (next - prev));
+            }
+        }
+    }
+    out
+}
+
+/// Align irregular readings onto a regular `interval`-minute grid: average the
+/// readings falling in each slot, then interpolate across slots that are empty
+/// for less than `max_gap` minutes. Longer gaps stay null.
+fn resample_data(
+    df: DataFrame,
+    interval_minutes: i64,
+    max_gap_minutes: i64,
+) -> PolarsResult<DataFrame> {
+    let timestamp_col = "Timestamp (YYYY-MM-DDThh:mm:ss)";
+    let glucose_col = "Glucose Value (mg/dL)";
+    let every = Duration::parse(&format!("{interval_minutes}m"));
+
+    // Average readings into fixed slots, floored to the grid.
+    let slotted = df
+        .lazy()
+        .sort(
+            [timestamp_col],
+            SortMultipleOptions {
+                descending: vec![false],
+                nulls_last: vec![false],
+                multithreaded: true,
+                maintain_order: false,
+            },
+        )
+        .group_by_dynamic(
+            col(timestamp_col),
+            [],
+            DynamicGroupOptions {
+                every,
+                period: every,
+                offset: Duration::parse("0"),
+                label: Label::Left,
+                include_boundaries: false,
+                closed_window: ClosedWindow::Left,
+                start_by: StartBy::WindowBound,
+                ..Default::default()
+            },
+        )
+        .agg([col(glucose_col).mean()])
+        .collect()?;
+
+    // Upsample to a gap-free grid (empty slots become null), then interpolate
+    // only the short gaps.
+    let grid = slotted.upsample::<[String; 0]>([], timestamp_col, every)?;
+
+    let timestamps = grid.column(timestamp_col)?.clone();
+    let raw: Vec<Option<f64>> = grid[glucose_col].f64()?.into_iter().collect();
+    let filled = interpolate_short_gaps(&raw, interval_minutes, max_gap_minutes);
+
+    DataFrame::new(vec![
+        timestamps,
+        Series::new(glucose_col.into(), filled).into(),
+    ])?
+    .lazy()
+    .drop_nulls(None)
+    .collect()
+}
+
 fn calculate_hourly_stats(df: DataFrame) -> PolarsResult<DataFrame> {
     let timestamp_col = "Timestamp (YYYY-MM-DDThh:mm:ss)";
     let glucose_col = "Glucose Value (mg/dL)";
@@ -88,12 +342,282 @@ fn calculate_hourly_stats(df: DataFrame) -> PolarsResult<DataFrame> {
         .collect()
 }
 
-fn plot_hourly_stats(hourly_stats: DataFrame) -> Result<Chart, Box<dyn Error>> {
-    let hours: Vec<String> = hourly_stats["Hour"]
-        .i8()?
+/// Solve the dense linear system `A X = B` by Gauss-Jordan elimination with
+/// partial pivoting, where `B` may carry several right-hand-side columns.
+/// Returns `X`. The systems here are tiny (24×24), so a direct solve is fine.
+fn solve_dense(mut a: Vec<Vec<f64>>, mut b: Vec<Vec<f64>>) -> Vec<Vec<f64>> {
+    let n = a.len();
+    let m = b[0].len();
+    for col in 0..n {
+        let mut pivot = col;
+        for row in (col + 1)..n {
+            if a[row][col].abs() > a[pivot][col].abs() {
+                pivot = row;
+            }
+        }
+        a.swap(col, pivot);
+        b.swap(col, pivot);
+
+        let diag = a[col][col];
+        for j in col..n {
+            a[col][j] /= diag;
+        }
+        for j in 0..m {
+            b[col][j] /= diag;
+        }
+
+        for row in 0..n {
+            if row == col {
+                continue;
+            }
+            let factor = a[row][col];
+            if factor == 0.0 {
+                continue;
+            }
+            for j in col..n {
+                a[row][j] -= factor * a[col][j];
+            }
+            for j in 0..m {
+                b[row][j] -= factor * b[col][j];
+            }
+        }
+    }
+    b
+}
+
+/// Weight given to a missing knot's own row in [`cyclic_smoothing_fit`]'s
+/// linear system. Must stay strictly positive: a weight of 0 makes that row's
+/// diagonal collapse to `lambda * 0` whenever `lambda` is also 0 (an
+/// ordinary, user-reachable `--lambda 0` plus any missing hour in the padded
+/// 24-knot grid), which leaves the system singular and the whole fit NaN.
+const MISSING_KNOT_WEIGHT: f64 = 1e-6;
+
+/// Fill missing knots with their nearest present neighbor on the circle, so a
+/// missing knot has a sensible fallback value when the curvature penalty
+/// below is too weak (or absent, at `lambda = 0`) to bridge the gap itself.
+fn nearest_fill(y: &[Option<f64>]) -> Vec<f64> {
+    let n = y.len();
+    (0..n)
+        .map(|i| {
+            if let Some(value) = y[i] {
+                return value;
+            }
+            (1..=n / 2 + 1)
+                .find_map(|dist| y[(i + n - dist) % n].or(y[(i + dist) % n]))
+                .unwrap_or(0.0)
+        })
+        .collect()
+}
+
+/// Fit a cyclic cubic smoothing spline to noisy hourly observations `y` at
+/// equally spaced knots on a circle of period `y.len()`. Builds the periodic
+/// cubic-spline second-derivative penalty `K = Qᵀ R⁻¹ Q` and solves
+/// `(W + λK) f = W y` for the fitted knot values `f`, where `W` weights
+/// present knots at 1 and missing ones at [`MISSING_KNOT_WEIGHT`].
+fn cyclic_smoothing_fit(y: &[Option<f64>], lambda: f64) -> Vec<f64> {
+    let n = y.len();
+    // Second-difference operator Q and the consistency matrix R (both circulant).
+    let mut q = vec![vec![0.0; n]; n];
+    let mut r = vec![vec![0.0; n]; n];
+    for i in 0..n {
+        let prev = (i + n - 1) % n;
+        let next = (i + 1) % n;
+        q[i][prev] += 1.0;
+        q[i][i] -= 2.0;
+        q[i][next] += 1.0;
+        r[i][i] += 4.0 / 6.0;
+        r[i][prev] += 1.0 / 6.0;
+        r[i][next] += 1.0 / 6.0;
+    }
+
+    // X = R⁻¹ Q, then K = Qᵀ X. Missing knots get a small floor weight, so the
+    // fit relies mostly on the curvature penalty to bridge them, but the
+    // system stays non-singular even when lambda is 0.
+    let x = solve_dense(r, q.clone());
+    let filled = nearest_fill(y);
+    let weight = |i: usize| if y[i].is_some() { 1.0 } else { MISSING_KNOT_WEIGHT };
+    let mut a = vec![vec![0.0; n]; n];
+    for i in 0..n {
+        for j in 0..n {
+            let mut sum = 0.0;
+            for l in 0..n {
+                sum += q[l][i] * x[l][j];
+            }
+            a[i][j] = lambda * sum;
+        }
+        a[i][i] += weight(i);
+    }
+
+    let rhs = (0..n).map(|i| vec![weight(i) * filled[i]]).collect();
+    solve_dense(a, rhs).into_iter().map(|row| row[0]).collect()
+}
+
+/// Evaluate the periodic interpolating cubic spline through knot values `f` on a
+/// fine grid of the given `step`, returning `[x, y]` pairs over one full period
+/// (endpoint included so the curve closes at the midnight wrap-around).
+fn periodic_spline_curve(f: &[f64], step: f64) -> Vec<Vec<f64>> {
+    let n = f.len();
+    // Solve for the second derivatives m under periodic boundary conditions.
+    let mut a = vec![vec![0.0; n]; n];
+    let mut rhs = vec![vec![0.0; 1]; n];
+    for i in 0..n {
+        let prev = (i + n - 1) % n;
+        let next = (i + 1) % n;
+        a[i][prev] += 1.0;
+        a[i][i] += 4.0;
+        a[i][next] += 1.0;
+        rhs[i][0] = 6.0 * (f[next] - 2.0 * f[i] + f[prev]);
+    }
+    let m: Vec<f64> = solve_dense(a, rhs).into_iter().map(|row| row[0]).collect();
+
+    // Sample the piecewise cubic (unit knot spacing) across [0, n].
+    let mut points = Vec::new();
+    let mut x = 0.0;
+    while x < n as f64 + 1e-9 {
+        let i = (x.floor() as usize) % n;
+        let next = (i + 1) % n;
+        let t = x - x.floor();
+        let value = m[i] * (1.0 - t).powi(3) / 6.0
+            + m[next] * t.powi(3) / 6.0
+            + (f[i] - m[i] / 6.0) * (1.0 - t)
+            + (f[next] - m[next] / 6.0) * t;
+        points.push(vec![x, value]);
+        x += step;
+    }
+    points
+}
+
+/// Extract the cleaned readings as `(time-of-day, glucose)` pairs, ordered
+/// chronologically. The time-of-day coordinate is `hour + minute / 60`, so —
+/// like the rest of the AGP chart — every calendar day is folded onto the same
+/// 00:00–24:00 span rather than drawn on a chronological axis. Each midnight
+/// wrap is a point where the coordinate resets to 0, which [`zone_segments`]
+/// treats as a segment break.
+fn time_of_day_points(df: &DataFrame) -> PolarsResult<Vec<(f64, f64)>> {
+    let timestamp_col = "Timestamp (YYYY-MM-DDThh:mm:ss)";
+    let glucose_col = "Glucose Value (mg/dL)";
+
+    let projected = df
+        .clone()
+        .lazy()
+        .sort(
+            [timestamp_col],
+            SortMultipleOptions {
+                descending: vec![false],
+                nulls_last: vec![false],
+                multithreaded: true,
+                maintain_order: false,
+            },
+        )
+        .with_column(
+            (col(timestamp_col).dt().hour().cast(DataType::Float64)
+                + col(timestamp_col).dt().minute().cast(DataType::Float64) / lit(60.0))
+            .alias("Time of Day"),
+        )
+        .collect()?;
+
+    let times = projected["Time of Day"].f64()?;
+    let values = projected[glucose_col].f64()?;
+    Ok(times
         .into_no_null_iter()
-        .map(|hour| hour.to_string())
-        .collect();
+        .zip(values.into_no_null_iter())
+        .collect())
+}
+
+/// Split a chronological glucose trace into contiguous single-zone segments,
+/// inserting an interpolated point wherever the signal crosses a threshold so
+/// that adjacent colored segments meet exactly on the threshold line. A drop in
+/// the time-of-day coordinate (a midnight wrap) also breaks the segment.
+fn zone_segments(points: &[(f64, f64)], thresholds: Thresholds) -> Vec<(TargetZone, Vec<Vec<f64>>)> {
+    let mut segments: Vec<(TargetZone, Vec<Vec<f64>>)> = Vec::new();
+    if points.is_empty() {
+        return segments;
+    }
+
+    let (x0, y0) = points[0];
+    let mut cur_zone = TargetZone::of(y0, thresholds);
+    let mut cur: Vec<Vec<f64>> = vec![vec![x0, y0]];
+
+    for window in points.windows(2) {
+        let (x0, y0) = window[0];
+        let (x1, y1) = window[1];
+
+        if x1 < x0 {
+            segments.push((cur_zone, std::mem::take(&mut cur)));
+            cur_zone = TargetZone::of(y1, thresholds);
+            cur.push(vec![x1, y1]);
+            continue;
+        }
+
+        // Boundaries of this pair: the two endpoints plus any threshold crossings.
+        let mut crossings: Vec<(f64, f64)> = Vec::new();
+        for t in [thresholds.low, thresholds.high] {
+            if t > y0.min(y1) && t < y0.max(y1) {
+                let frac = (t - y0) / (y1 - y0);
+                crossings.push((x0 + frac * (x1 - x0), t));
+            }
+        }
+        crossings.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+
+        let mut bounds = vec![(x0, y0)];
+        bounds.extend(crossings);
+        bounds.push((x1, y1));
+
+        for pair in bounds.windows(2) {
+            let (ax, ay) = pair[0];
+            let (bx, by) = pair[1];
+            let zone = TargetZone::of((ay + by) / 2.0, thresholds);
+            if zone != cur_zone {
+                segments.push((cur_zone, std::mem::take(&mut cur)));
+                cur.push(vec![ax, ay]);
+                cur_zone = zone;
+            }
+            cur.push(vec![bx, by]);
+        }
+    }
+
+    segments.push((cur_zone, cur));
+    segments
+}
+
+/// Build the zone-colored raw-trace series, overlaid on the AGP time-of-day
+/// axis (all days folded onto 00:00–24:00, not a chronological trace). The first
+/// segment of each zone carries the legend name; later segments reuse the color
+/// unnamed so the legend stays to a single entry per zone.
+fn zone_trace_series(points: &[(f64, f64)], thresholds: Thresholds) -> Vec<Line> {
+    let mut named = [false; 3];
+    zone_segments(points, thresholds)
+        .into_iter()
+        .filter(|(_, segment)| segment.len() > 1)
+        .map(|(zone, segment)| {
+            let slot = match zone {
+                TargetZone::Below => 0,
+                TargetZone::InRange => 1,
+                TargetZone::Above => 2,
+            };
+            let mut line = Line::new()
+                .data(segment)
+                .x_axis_index(1)
+                .show_symbol(false)
+                .line_style(LineStyle::new().color(zone.color()).width(1.5));
+            if !named[slot] {
+                named[slot] = true;
+                line = line.name(zone.name());
+            }
+            line
+        })
+        .collect()
+}
+
+fn plot_hourly_stats(
+    hourly_stats: DataFrame,
+    raw: &DataFrame,
+    thresholds: Thresholds,
+    lambda: f64,
+    unit: GlucoseUnit,
+) -> Result<Chart, Box<dyn Error>> {
+    let hour_index: Vec<i8> = hourly_stats["Hour"].i8()?.into_no_null_iter().collect();
+    let hours: Vec<String> = hour_index.iter().map(|hour| hour.to_string()).collect();
     let mean_values: Vec<f64> = hourly_stats["Mean Glucose Value"]
         .f64()?
         .into_no_null_iter()
@@ -114,140 +638,989 @@ fn plot_hourly_stats(hourly_stats: DataFrame) -> Result<Chart, Box<dyn Error>> {
         .f64()?
         .into_no_null_iter()
         .collect();
-    let area_25_75: Vec<f64> = percentile_25
+    let max_value = percentile_95
+        .clone()
+        .into_iter()
+        .reduce(f64::max)
+        .ok_or("no hourly statistics to plot")?;
+    let interval = unit.axis_interval();
+
+    // Scatter the present hours onto a full 0..23 knot grid (null for missing
+    // hours) so the periodic fit always spans the whole 24-hour cycle, even for
+    // short or sparse subsets.
+    let pad = |values: &[f64]| -> Vec<Option<f64>> {
+        let mut grid = vec![None; 24];
+        for (hour, value) in hour_index.iter().zip(values.iter()) {
+            grid[*hour as usize] = Some(*value);
+        }
+        grid
+    };
+
+    // Fit a genuine cyclic smoothing spline to each hourly curve and evaluate it
+    // on a fine grid for plotting on the continuous axis.
+    let mean_curve = periodic_spline_curve(&cyclic_smoothing_fit(&pad(&mean_values), lambda), 0.1);
+    let p5_curve = periodic_spline_curve(&cyclic_smoothing_fit(&pad(&percentile_5), lambda), 0.1);
+    let p25_curve = periodic_spline_curve(&cyclic_smoothing_fit(&pad(&percentile_25), lambda), 0.1);
+    let p75_curve = periodic_spline_curve(&cyclic_smoothing_fit(&pad(&percentile_75), lambda), 0.1);
+    let p95_curve = periodic_spline_curve(&cyclic_smoothing_fit(&pad(&percentile_95), lambda), 0.1);
+
+    // Confidence bands built from the same fitted splines (base edge + stacked
+    // width), so each band sits exactly under the percentile line bounding it.
+    let band_5_95: Vec<Vec<f64>> = p5_curve
         .iter()
-        .zip(percentile_75.iter())
-        .map(|(l, h)| h - l)
+        .zip(p95_curve.iter())
+        .map(|(low, high)| vec![low[0], high[1] - low[1]])
         .collect();
-    let area_5_95: Vec<f64> = percentile_5
+    let band_25_75: Vec<Vec<f64>> = p25_curve
         .iter()
-        .zip(percentile_95.iter())
-        .map(|(l, h)| h - l)
+        .zip(p75_curve.iter())
+        .map(|(low, high)| vec![low[0], high[1] - low[1]])
         .collect();
-    let max_value = percentile_95.clone().into_iter().reduce(f64::max).unwrap();
 
-    return Ok(Chart::new()
+    let raw_points = time_of_day_points(raw)?;
+
+    let mut chart = Chart::new()
         .title(Title::new().text("Hourly Mean Glucose Levels"))
         .x_axis(Axis::new().name("Hour of the Day").data(hours))
+        // Second, continuous axis carrying the raw zone-colored time-of-day trace.
+        .x_axis(
+            Axis::new()
+                .type_(AxisType::Value)
+                .min(0)
+                .max(24)
+                .show(false),
+        )
         .y_axis(
             Axis::new()
-                .name("Glucose Value (mg/dL)")
-                .interval(25)
+                .name(format!("Glucose Value ({})", unit.label()))
+                .interval(interval)
                 .min(0)
-                .max((max_value + 50.0) - (max_value % 25.0)), // adjust the maximum of the graph to the y grid interval
+                .max((max_value + 2.0 * interval) - (max_value % interval)), // adjust the maximum of the graph to the y grid interval
         )
         .legend(Legend::new().top("bottom"))
         .background_color("#fff")
         .grid(Grid::new())
         .series(
-            // Draw the band of 5 to 95 percentile interval
+            // Draw the band of 5 to 95 percentile interval from the fitted spline
             Line::new()
-                .data(percentile_5.clone())
+                .data(p5_curve.clone())
+                .x_axis_index(1)
                 .line_style(LineStyle::new().opacity(0))
                 .stack("confidence-5-95-band")
-                .smooth(0.5)
                 .show_symbol(false),
         )
         .series(
             Line::new()
-                .data(area_5_95)
+                .data(band_5_95)
+                .x_axis_index(1)
                 .line_style(LineStyle::new().opacity(0))
                 .area_style(AreaStyle::new().color("#ddd").opacity(0.5))
                 .stack("confidence-5-95-band")
-                .smooth(0.5)
                 .show_symbol(false),
         )
         .series(
-            // Draw the 5th percentile line
+            // Draw the 5th percentile line as a fitted cyclic spline
             Line::new()
                 .name("5th Percentile")
-                .data(percentile_5)
+                .data(p5_curve)
+                .x_axis_index(1)
                 .item_style(ItemStyle::new().opacity(0))
                 .line_style(LineStyle::new().color("#d33"))
-                .smooth(0.5),
+                .show_symbol(false),
         )
         .series(
-            // Draw the 95th percentile line
+            // Draw the 95th percentile line as a fitted cyclic spline
             Line::new()
                 .name("95th Percentile")
-                .data(percentile_95)
+                .data(p95_curve)
+                .x_axis_index(1)
                 .item_style(ItemStyle::new().opacity(0))
                 .line_style(LineStyle::new().color("#833"))
-                .smooth(0.5),
+                .show_symbol(false),
         )
         .series(
-            // Draw the band of 25 to 75 percentile interval
+            // Draw the band of 25 to 75 percentile interval from the fitted spline
             Line::new()
-                .data(percentile_25.clone())
+                .data(p25_curve.clone())
+                .x_axis_index(1)
                 .line_style(LineStyle::new().opacity(0))
                 .stack("confidence-25-75-band")
-                .smooth(0.5)
                 .show_symbol(false),
         )
         .series(
             Line::new()
-                .data(area_25_75)
+                .data(band_25_75)
+                .x_axis_index(1)
                 .line_style(LineStyle::new().opacity(0))
                 .area_style(AreaStyle::new().color("#ccc").opacity(0.65))
                 .stack("confidence-25-75-band")
-                .smooth(0.5)
                 .show_symbol(false),
         )
         .series(
-            // Draw the 25th percentile line
+            // Draw the 25th percentile line as a fitted cyclic spline
             Line::new()
                 .name("25th Percentile")
-                .data(percentile_25)
+                .data(p25_curve)
+                .x_axis_index(1)
                 .item_style(ItemStyle::new().opacity(0))
                 .line_style(LineStyle::new().color("#33d"))
-                .smooth(0.5),
+                .show_symbol(false),
         )
         .series(
-            // Draw the 75th percentile line
+            // Draw the 75th percentile line as a fitted cyclic spline
             Line::new()
                 .name("75th Percentile")
-                .data(percentile_75)
+                .data(p75_curve)
+                .x_axis_index(1)
                 .item_style(ItemStyle::new().opacity(0))
                 .line_style(LineStyle::new().color("#338"))
-                .smooth(0.5),
+                .show_symbol(false),
         )
         .series(
-            // Draw the mean line
+            // Draw the mean line as a fitted cyclic spline
             Line::new()
                 .name("Mean Glucose")
-                .data(mean_values)
+                .data(mean_curve)
+                .x_axis_index(1)
                 .item_style(ItemStyle::new().opacity(0))
                 .line_style(LineStyle::new().color("#4d4"))
-                .smooth(0.5),
+                .show_symbol(false),
+        );
+
+    // Overlay the raw trace, colored per target zone, on the continuous axis and
+    // mark the low/high thresholds as horizontal reference lines.
+    let mut zone_series = zone_trace_series(&raw_points, thresholds);
+    if let Some(first) = zone_series.first_mut() {
+        *first = std::mem::replace(first, Line::new()).mark_line(
+            MarkLine::new().data(vec![
+                MarkLineVariant::Simple(MarkLineData::new().y_axis(thresholds.low)),
+                MarkLineVariant::Simple(MarkLineData::new().y_axis(thresholds.high)),
+            ]),
+        );
+    }
+    for line in zone_series {
+        chart = chart.series(line);
+    }
+
+    Ok(chart)
+}
+
+/// The standard CGM summary metrics derived from the cleaned series.
+struct GlucoseMetrics {
+    mean: f64,
+    cv: f64,
+    gmi: f64,
+    time_below: f64,
+    time_in_range: f64,
+    time_above: f64,
+    unit: GlucoseUnit,
+}
+
+/// Compute Time-in-Range, mean glucose, coefficient of variation and the
+/// Glucose Management Indicator from the cleaned readings (in the active unit).
+fn compute_metrics(
+    df: &DataFrame,
+    thresholds: Thresholds,
+    unit: GlucoseUnit,
+) -> PolarsResult<GlucoseMetrics> {
+    let glucose_col = "Glucose Value (mg/dL)";
+    let values: Vec<f64> = df[glucose_col].f64()?.into_no_null_iter().collect();
+
+    if values.len() < 2 {
+        return Err(PolarsError::ComputeError(
+            "at least two readings are required to compute CGM metrics".into(),
         ));
+    }
+
+    let n = values.len() as f64;
+    let mean = values.iter().sum::<f64>() / n;
+    // Sample standard deviation.
+    let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (n - 1.0);
+    let sd = variance.sqrt();
+
+    let below = values.iter().filter(|&&v| v < thresholds.low).count() as f64;
+    let above = values.iter().filter(|&&v| v > thresholds.high).count() as f64;
+    let in_range = n - below - above;
+
+    Ok(GlucoseMetrics {
+        mean,
+        cv: 100.0 * sd / mean,
+        gmi: 3.31 + 0.02392 * unit.to_mgdl(mean),
+        time_below: 100.0 * below / n,
+        time_in_range: 100.0 * in_range / n,
+        time_above: 100.0 * above / n,
+        unit,
+    })
+}
+
+impl GlucoseMetrics {
+    /// One line per metric, shared by the printed table and the chart block.
+    fn lines(&self) -> Vec<String> {
+        let label = self.unit.label();
+        vec![
+            format!("Mean glucose: {:.1} {label}", self.mean),
+            format!("GMI: {:.1}%", self.gmi),
+            format!("CV: {:.1}%", self.cv),
+            format!("Time below target: {:.1}%", self.time_below),
+            format!("Time in target: {:.1}%", self.time_in_range),
+            format!("Time above target: {:.1}%", self.time_above),
+        ]
+    }
+
+    fn print_table(&self) {
+        println!("CGM summary");
+        println!("-----------");
+        for line in self.lines() {
+            println!("{line}");
+        }
+    }
+}
+
+/// Render the metrics as a text block in the top-right corner of the chart.
+fn annotate_metrics(chart: Chart, metrics: &GlucoseMetrics) -> Chart {
+    chart.title(
+        Title::new()
+            .text("CGM summary")
+            .subtext(metrics.lines().join("\n"))
+            .right("2%")
+            .top("5%"),
+    )
+}
+
+/// Generate a distinct color for entry `index` of `count` by walking the hue
+/// circle at 65% saturation, so each overlaid day gets its own line color.
+fn palette_color(index: usize, count: usize) -> String {
+    let hue = if count == 0 {
+        0.0
+    } else {
+        360.0 * index as f64 / count as f64
+    };
+    // HSL -> RGB at S = 0.65, L = 0.5.
+    let (s, l): (f64, f64) = (0.65, 0.5);
+    let c = (1.0 - (2.0 * l - 1.0).abs()) * s;
+    let h = hue / 60.0;
+    let x = c * (1.0 - (h % 2.0 - 1.0).abs());
+    let (r, g, b) = match h as u32 {
+        0 => (c, x, 0.0),
+        1 => (x, c, 0.0),
+        2 => (0.0, c, x),
+        3 => (0.0, x, c),
+        4 => (x, 0.0, c),
+        _ => (c, 0.0, x),
+    };
+    let m = l - c / 2.0;
+    format!(
+        "#{:02x}{:02x}{:02x}",
+        ((r + m) * 255.0).round() as u8,
+        ((g + m) * 255.0).round() as u8,
+        ((b + m) * 255.0).round() as u8,
+    )
+}
+
+/// Group the cleaned readings by calendar date, returning one `(date, points)`
+/// curve per day where points are `[time-of-day, glucose]`, ordered by date.
+fn daily_curves(df: &DataFrame) -> PolarsResult<Vec<(String, Vec<Vec<f64>>)>> {
+    let timestamp_col = "Timestamp (YYYY-MM-DDThh:mm:ss)";
+    let glucose_col = "Glucose Value (mg/dL)";
+
+    let projected = df
+        .clone()
+        .lazy()
+        .sort(
+            [timestamp_col],
+            SortMultipleOptions {
+                descending: vec![false],
+                nulls_last: vec![false],
+                multithreaded: true,
+                maintain_order: false,
+            },
+        )
+        .with_column(
+            col(timestamp_col)
+                .dt()
+                .strftime("%Y-%m-%d")
+                .alias("Date"),
+        )
+        .with_column(
+            (col(timestamp_col).dt().hour().cast(DataType::Float64)
+                + col(timestamp_col).dt().minute().cast(DataType::Float64) / lit(60.0))
+            .alias("Time of Day"),
+        )
+        .collect()?;
+
+    let dates = projected["Date"].str()?;
+    let times = projected["Time of Day"].f64()?;
+    let values = projected[glucose_col].f64()?;
+
+    let mut curves: Vec<(String, Vec<Vec<f64>>)> = Vec::new();
+    for ((date, tod), value) in dates
+        .into_no_null_iter()
+        .zip(times.into_no_null_iter())
+        .zip(values.into_no_null_iter())
+    {
+        if curves.last().map(|(d, _)| d.as_str()) != Some(date) {
+            curves.push((date.to_string(), Vec::new()));
+        }
+        curves
+            .last_mut()
+            .expect("at least one curve was just pushed")
+            .1
+            .push(vec![tod, value]);
+    }
+    Ok(curves)
+}
+
+/// Plot an AGP-style overlay with one colored line per calendar day on a shared
+/// 00:00–24:00 time-of-day axis, so day-to-day patterns can be compared.
+fn plot_daily_overlay(raw: &DataFrame, unit: GlucoseUnit) -> Result<Chart, Box<dyn Error>> {
+    let curves = daily_curves(raw)?;
+    let count = curves.len();
+    let interval = unit.axis_interval();
+
+    let max_value = curves
+        .iter()
+        .flat_map(|(_, points)| points.iter().map(|p| p[1]))
+        .reduce(f64::max)
+        .unwrap_or(0.0);
+
+    let mut chart = Chart::new()
+        .title(Title::new().text("Daily Glucose Overlay"))
+        .x_axis(
+            Axis::new()
+                .name("Time of Day")
+                .type_(AxisType::Value)
+                .min(0)
+                .max(24)
+                .interval(3),
+        )
+        .y_axis(
+            Axis::new()
+                .name(format!("Glucose Value ({})", unit.label()))
+                .interval(interval)
+                .min(0)
+                .max((max_value + 2.0 * interval) - (max_value % interval)),
+        )
+        .legend(Legend::new().top("bottom"))
+        .background_color("#fff")
+        .grid(Grid::new());
+
+    for (index, (date, points)) in curves.into_iter().enumerate() {
+        chart = chart.series(
+            Line::new()
+                .name(date)
+                .data(points)
+                .show_symbol(false)
+                .line_style(LineStyle::new().color(palette_color(index, count)).width(1.0)),
+        );
+    }
+
+    Ok(chart)
+}
+
+/// Symbol used to mark the `index`-th distinct event type.
+fn event_symbol(index: usize) -> Symbol {
+    match index % 5 {
+        0 => Symbol::Circle,
+        1 => Symbol::Diamond,
+        2 => Symbol::Triangle,
+        3 => Symbol::Rect,
+        _ => Symbol::Pin,
+    }
 }
 
-fn save_chart_as_file(chart: Chart) -> Result<(), Box<dyn Error>> {
-    let file_name = "glucose_levels.png";
+/// Extract activity events as `(type, time-of-day, note)`, ordered by type so
+/// each type maps to a stable symbol/color.
+fn activity_events(df: &DataFrame) -> PolarsResult<Vec<(String, f64, String)>> {
+    let types = df.column("Type")?.str()?;
+    let tod = df
+        .clone()
+        .lazy()
+        .select([(col("Timestamp").dt().hour().cast(DataType::Float64)
+            + col("Timestamp").dt().minute().cast(DataType::Float64) / lit(60.0))
+        .alias("Time of Day")])
+        .collect()?;
+    let tod = tod["Time of Day"].f64()?;
+    let notes = df.column("Note").ok().and_then(|c| c.str().ok().cloned());
+
+    let mut events: Vec<(String, f64, String)> = Vec::new();
+    for (row, (event_type, time)) in types
+        .into_iter()
+        .zip(tod.into_iter())
+        .enumerate()
+    {
+        let (Some(event_type), Some(time)) = (event_type, time) else {
+            continue;
+        };
+        let note = notes
+            .as_ref()
+            .and_then(|n| n.get(row))
+            .unwrap_or("")
+            .to_string();
+        events.push((event_type.to_string(), time, note));
+    }
+    events.sort_by(|a, b| a.0.cmp(&b.0));
+    Ok(events)
+}
 
-    ImageRenderer::new(1400, 800)
-        .save_format(ImageFormat::Png, &chart, file_name)
-        .map_err(|err| format!("Error rendering the chart: {:?}", err))?;
+/// Overlay activity events on the chart as per-type annotation markers, anchored
+/// to the time-of-day axis at the high threshold line.
+fn overlay_activity(chart: Chart, events: &[(String, f64, String)], thresholds: Thresholds) -> Chart {
+    let mut types: Vec<&str> = events.iter().map(|(t, _, _)| t.as_str()).collect();
+    types.dedup();
 
-    println!("Plot has been saved as {file_name}");
+    let mut chart = chart;
+    for (index, event_type) in types.iter().enumerate() {
+        let color = palette_color(index, types.len());
+        let marks: Vec<MarkPointData> = events
+            .iter()
+            .filter(|(t, _, _)| t == event_type)
+            .map(|(_, time, note)| {
+                let label = if note.is_empty() {
+                    event_type.to_string()
+                } else {
+                    format!("{event_type}: {note}")
+                };
+                MarkPointData::new()
+                    .name(label)
+                    .x_axis(*time)
+                    .y_axis(thresholds.high)
+                    .symbol(event_symbol(index))
+            })
+            .collect();
 
+        chart = chart.series(
+            Line::new()
+                .name(*event_type)
+                .data(Vec::<f64>::new())
+                .x_axis_index(1)
+                .item_style(ItemStyle::new().color(color))
+                .mark_point(MarkPoint::new().data(marks)),
+        );
+    }
+    chart
+}
+
+/// Output format for the rendered report.
+#[derive(Clone, Copy)]
+enum OutputFormat {
+    Png,
+    Svg,
+    Pdf,
+}
+
+impl OutputFormat {
+    fn parse(value: &str) -> Result<OutputFormat, String> {
+        match value.to_lowercase().as_str() {
+            "png" => Ok(OutputFormat::Png),
+            "svg" => Ok(OutputFormat::Svg),
+            "pdf" => Ok(OutputFormat::Pdf),
+            other => Err(format!("Unknown format: {other}")),
+        }
+    }
+
+    fn extension(self) -> &'static str {
+        match self {
+            OutputFormat::Png => "png",
+            OutputFormat::Svg => "svg",
+            OutputFormat::Pdf => "pdf",
+        }
+    }
+}
+
+/// A single page of the report: a titled chart.
+struct ReportPage {
+    title: String,
+    chart: Chart,
+}
+
+/// Turn a page title into a filesystem-friendly slug.
+fn slug(title: &str) -> String {
+    title
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c.to_ascii_lowercase() } else { '-' })
+        .collect()
+}
+
+/// Emit the report. PNG/SVG produce one file per page; PDF bundles every page
+/// into a single multi-page document.
+fn save_report(pages: Vec<ReportPage>, format: OutputFormat) -> Result<(), Box<dyn Error>> {
+    match format {
+        OutputFormat::Png | OutputFormat::Svg => {
+            let image_format = match format {
+                OutputFormat::Svg => ImageFormat::Svg,
+                _ => ImageFormat::Png,
+            };
+            for (index, page) in pages.iter().enumerate() {
+                let file_name =
+                    format!("glucose-report-{:02}-{}.{}", index + 1, slug(&page.title), format.extension());
+                ImageRenderer::new(1400, 800)
+                    .save_format(image_format, &page.chart, &file_name)
+                    .map_err(|err| format!("Error rendering the chart: {:?}", err))?;
+                println!("Saved {file_name}");
+            }
+        }
+        OutputFormat::Pdf => save_report_as_pdf(&pages, "glucose-report.pdf")?,
+    }
     Ok(())
 }
 
+/// Render each page to a raster and place it on its own landscape A4 PDF page.
+fn save_report_as_pdf(pages: &[ReportPage], file_name: &str) -> Result<(), Box<dyn Error>> {
+    use printpdf::{Image, ImageTransform, Mm, PdfDocument};
+    use std::fs::File;
+    use std::io::BufWriter;
+
+    // A4 landscape, with the charts rendered at the usual 1400×800 aspect.
+    let (page_w, page_h) = (Mm(297.0), Mm(210.0));
+    let (doc, first_page, first_layer) =
+        PdfDocument::new("CGM Report", page_w, page_h, "Layer 1");
+
+    for (index, page) in pages.iter().enumerate() {
+        let (page_ref, layer_ref) = if index == 0 {
+            (first_page, first_layer.clone())
+        } else {
+            doc.add_page(page_w, page_h, "Layer 1")
+        };
+        let layer = doc.get_page(page_ref).get_layer(layer_ref);
+
+        let png = ImageRenderer::new(1400, 800)
+            .render_format(ImageFormat::Png, &page.chart)
+            .map_err(|err| format!("Error rendering the chart: {:?}", err))?;
+        let decoded = image::load_from_memory(&png)?;
+        let image = Image::from_dynamic_image(&decoded);
+
+        // Scale the 1400×800 raster (rendered at 300 dpi ≈ 118 mm wide) to fill
+        // the landscape page width, and centre it vertically.
+        image.add_to_layer(
+            layer,
+            ImageTransform {
+                translate_x: Some(Mm(10.0)),
+                translate_y: Some(Mm(27.1)),
+                scale_x: Some(2.3),
+                scale_y: Some(2.3),
+                dpi: Some(300.0),
+                ..Default::default()
+            },
+        );
+    }
+
+    doc.save(&mut BufWriter::new(File::create(file_name)?))?;
+    println!("Saved {file_name}");
+    Ok(())
+}
+
+/// Build the hourly AGP chart (resample → stats → spline plot), optionally
+/// overlaying activity events.
+fn build_hourly_chart(
+    glucose: &DataFrame,
+    thresholds: Thresholds,
+    lambda: f64,
+    unit: GlucoseUnit,
+    interval_minutes: i64,
+    max_gap_minutes: i64,
+    activity: Option<&DataFrame>,
+) -> Result<Chart, Box<dyn Error>> {
+    let resampled = resample_data(glucose.clone(), interval_minutes, max_gap_minutes)?;
+    let hourly_stats = calculate_hourly_stats(resampled)?;
+    let mut chart = plot_hourly_stats(hourly_stats, glucose, thresholds, lambda, unit)?;
+    if let Some(events) = activity {
+        chart = overlay_activity(chart, &activity_events(events)?, thresholds);
+    }
+    Ok(chart)
+}
+
+/// Restrict the readings to a single ISO weekday (1 = Monday … 7 = Sunday).
+fn filter_weekday(df: &DataFrame, weekday: i8) -> PolarsResult<DataFrame> {
+    let timestamp_col = "Timestamp (YYYY-MM-DDThh:mm:ss)";
+    df.clone()
+        .lazy()
+        .filter(col(timestamp_col).dt().weekday().eq(lit(weekday)))
+        .collect()
+}
+
+/// Plot a single day's glucose curve on a 00:00–24:00 time-of-day axis.
+fn plot_single_day(
+    date: &str,
+    points: Vec<Vec<f64>>,
+    unit: GlucoseUnit,
+) -> Result<Chart, Box<dyn Error>> {
+    let interval = unit.axis_interval();
+    let max_value = points.iter().map(|p| p[1]).reduce(f64::max).unwrap_or(0.0);
+
+    Ok(Chart::new()
+        .title(Title::new().text(format!("Glucose — {date}")))
+        .x_axis(
+            Axis::new()
+                .name("Time of Day")
+                .type_(AxisType::Value)
+                .min(0)
+                .max(24)
+                .interval(3),
+        )
+        .y_axis(
+            Axis::new()
+                .name(format!("Glucose Value ({})", unit.label()))
+                .interval(interval)
+                .min(0)
+                .max((max_value + 2.0 * interval) - (max_value % interval)),
+        )
+        .background_color("#fff")
+        .grid(Grid::new())
+        .series(
+            Line::new()
+                .data(points)
+                .show_symbol(false)
+                .line_style(LineStyle::new().color("#4d4").width(1.0)),
+        ))
+}
+
+/// Expand the requested chart kinds into report pages. `perday` paginates one
+/// calendar day per page; `weekday` emits one hourly chart per weekday.
+fn build_pages(
+    kinds: &[String],
+    glucose: &DataFrame,
+    metrics: &GlucoseMetrics,
+    thresholds: Thresholds,
+    lambda: f64,
+    unit: GlucoseUnit,
+    interval_minutes: i64,
+    max_gap_minutes: i64,
+    activity: Option<&DataFrame>,
+) -> Result<Vec<ReportPage>, Box<dyn Error>> {
+    const WEEKDAYS: [&str; 7] = [
+        "Monday",
+        "Tuesday",
+        "Wednesday",
+        "Thursday",
+        "Friday",
+        "Saturday",
+        "Sunday",
+    ];
+
+    let mut pages = Vec::new();
+    for kind in kinds {
+        match kind.as_str() {
+            "hourly" => pages.push(ReportPage {
+                title: "Hourly AGP".to_string(),
+                chart: annotate_metrics(
+                    build_hourly_chart(
+                        glucose,
+                        thresholds,
+                        lambda,
+                        unit,
+                        interval_minutes,
+                        max_gap_minutes,
+                        activity,
+                    )?,
+                    metrics,
+                ),
+            }),
+            "daily" => pages.push(ReportPage {
+                title: "Daily Overlay".to_string(),
+                chart: plot_daily_overlay(glucose, unit)?,
+            }),
+            "weekday" => {
+                for (index, name) in WEEKDAYS.iter().enumerate() {
+                    let subset = filter_weekday(glucose, index as i8 + 1)?;
+                    if subset.height() == 0 {
+                        continue;
+                    }
+                    pages.push(ReportPage {
+                        title: format!("Weekday — {name}"),
+                        chart: build_hourly_chart(
+                            &subset,
+                            thresholds,
+                            lambda,
+                            unit,
+                            interval_minutes,
+                            max_gap_minutes,
+                            activity,
+                        )?,
+                    });
+                }
+            }
+            "perday" => {
+                for (date, points) in daily_curves(glucose)? {
+                    pages.push(ReportPage {
+                        title: format!("Day — {date}"),
+                        chart: plot_single_day(&date, points, unit)?,
+                    });
+                }
+            }
+            "metrics" => pages.push(ReportPage {
+                title: "CGM Summary".to_string(),
+                chart: annotate_metrics(Chart::new().background_color("#fff"), metrics),
+            }),
+            other => return Err(format!("Unknown chart: {other}").into()),
+        }
+    }
+    Ok(pages)
+}
+
+/// Parse a `YYYY-MM-DD` window bound into epoch milliseconds. `end_of_day`
+/// advances to the following midnight so `--until` is an inclusive day.
+fn parse_window_bound(value: &str, end_of_day: bool) -> Result<i64, Box<dyn Error>> {
+    let date = NaiveDate::parse_from_str(value, "%Y-%m-%d")?;
+    let date = if end_of_day {
+        date + chrono::Duration::days(1)
+    } else {
+        date
+    };
+    Ok(date
+        .and_hms_opt(0, 0, 0)
+        .expect("midnight is always valid")
+        .and_utc()
+        .timestamp_millis())
+}
+
 fn main() -> Result<(), Box<dyn Error>> {
     let args: Vec<String> = std::env::args().collect();
-    if args.len() != 2 {
-        eprintln!("Usage: {} <csv_file_path>", args[0]);
-        std::process::exit(1);
+
+    let mut file_path: Option<String> = None;
+    let mut unit = GlucoseUnit::MgDl;
+    let mut low: Option<f64> = None;
+    let mut high: Option<f64> = None;
+    let mut chart_kind = String::from("hourly");
+    let mut interval_minutes: i64 = 15;
+    let mut max_gap_minutes: i64 = 30;
+    let mut lambda: f64 = 1.0;
+    let mut activity_path: Option<String> = None;
+    let mut since: Option<String> = None;
+    let mut until: Option<String> = None;
+    let mut format = OutputFormat::Png;
+    let mut charts: Option<Vec<String>> = None;
+
+    let mut i = 1;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--units" => {
+                i += 1;
+                let value = args.get(i).ok_or("--units requires a value")?;
+                unit = GlucoseUnit::parse(value)?;
+            }
+            "--chart" => {
+                i += 1;
+                chart_kind = args.get(i).ok_or("--chart requires a value")?.to_string();
+            }
+            "--interval" => {
+                i += 1;
+                interval_minutes = args.get(i).ok_or("--interval requires a value")?.parse()?;
+            }
+            "--max-gap" => {
+                i += 1;
+                max_gap_minutes = args.get(i).ok_or("--max-gap requires a value")?.parse()?;
+            }
+            "--lambda" => {
+                i += 1;
+                lambda = args.get(i).ok_or("--lambda requires a value")?.parse()?;
+            }
+            "--activity" => {
+                i += 1;
+                activity_path = Some(args.get(i).ok_or("--activity requires a value")?.to_string());
+            }
+            "--since" => {
+                i += 1;
+                since = Some(args.get(i).ok_or("--since requires a value")?.to_string());
+            }
+            "--until" => {
+                i += 1;
+                until = Some(args.get(i).ok_or("--until requires a value")?.to_string());
+            }
+            "--format" => {
+                i += 1;
+                format = OutputFormat::parse(args.get(i).ok_or("--format requires a value")?)?;
+            }
+            "--charts" => {
+                i += 1;
+                charts = Some(
+                    args.get(i)
+                        .ok_or("--charts requires a value")?
+                        .split(',')
+                        .map(|s| s.trim().to_string())
+                        .collect(),
+                );
+            }
+            "--low" => {
+                i += 1;
+                low = Some(args.get(i).ok_or("--low requires a value")?.parse()?);
+            }
+            "--high" => {
+                i += 1;
+                high = Some(args.get(i).ok_or("--high requires a value")?.parse()?);
+            }
+            other => file_path = Some(other.to_string()),
+        }
+        i += 1;
     }
 
-    let file_path = &args[1];
+    if interval_minutes <= 0 {
+        return Err("--interval must be a positive number of minutes".into());
+    }
+    if max_gap_minutes < 0 {
+        return Err("--max-gap must not be negative".into());
+    }
 
-    let raw_values = read_exported_dexcom_values(file_path)?;
-    let glucose_levels = clean_data(raw_values)?;
-    let hourly_stats = calculate_hourly_stats(glucose_levels)?;
-    let hourly_chart = plot_hourly_stats(hourly_stats)?;
-    save_chart_as_file(hourly_chart)?;
+    let file_path = match file_path {
+        Some(path) => path,
+        None => {
+            eprintln!(
+                "Usage: {} [--units mg/dL|mmol/L] [--chart hourly|daily] [--low <v>] [--high <v>] [--lambda <v>] [--activity <csv_file>] [--since YYYY-MM-DD] [--until YYYY-MM-DD] [--format png|svg|pdf] [--charts hourly,daily,weekday,perday,metrics] <csv_file_path>",
+                args[0]
+            );
+            std::process::exit(1);
+        }
+    };
+
+    let defaults = Thresholds::defaults(unit);
+    let thresholds = Thresholds {
+        low: low.unwrap_or(defaults.low),
+        high: high.unwrap_or(defaults.high),
+    };
+
+    let since_ms = since.as_deref().map(|v| parse_window_bound(v, false)).transpose()?;
+    let until_ms = until.as_deref().map(|v| parse_window_bound(v, true)).transpose()?;
+
+    let timestamp_col = "Timestamp (YYYY-MM-DDThh:mm:ss)";
+    let raw_values = read_exported_dexcom_values(&file_path)?;
+    let glucose_levels = apply_window(clean_data(raw_values, unit)?, timestamp_col, since_ms, until_ms)?;
+
+    if glucose_levels.height() == 0 {
+        return Err("No glucose readings in the selected window".into());
+    }
+
+    // The activity events share the same window as the glucose data.
+    let activity = match activity_path {
+        Some(path) => Some(apply_window(read_activity_file(&path)?, "Timestamp", since_ms, until_ms)?),
+        None => None,
+    };
+
+    let metrics = compute_metrics(&glucose_levels, thresholds, unit)?;
+    metrics.print_table();
+
+    // `--charts` selects the report pages; otherwise fall back to the single
+    // `--chart` selector for backwards compatibility.
+    let kinds = charts.unwrap_or_else(|| vec![chart_kind]);
+    let pages = build_pages(
+        &kinds,
+        &glucose_levels,
+        &metrics,
+        thresholds,
+        lambda,
+        unit,
+        interval_minutes,
+        max_gap_minutes,
+        activity.as_ref(),
+    )?;
+
+    save_report(pages, format)?;
 
     Ok(())
 }
+
+#[cfg(test)]
+mod smoothing_tests {
+    use super::*;
+
+    #[test]
+    fn cyclic_fit_of_constant_input_is_constant() {
+        let y: Vec<Option<f64>> = vec![Some(5.0); 24];
+        let fit = cyclic_smoothing_fit(&y, 1.0);
+        for value in fit {
+            assert!((value - 5.0).abs() < 1e-9, "expected 5.0, got {value}");
+        }
+    }
+
+    #[test]
+    fn cyclic_fit_with_zero_lambda_interpolates_exactly() {
+        let y: Vec<Option<f64>> = (0..24).map(|i| Some(i as f64)).collect();
+        let fit = cyclic_smoothing_fit(&y, 0.0);
+        for (i, value) in fit.iter().enumerate() {
+            assert!((value - i as f64).abs() < 1e-6, "knot {i}: expected {i}, got {value}");
+        }
+    }
+
+    #[test]
+    fn cyclic_fit_with_missing_knot_and_zero_lambda_stays_finite() {
+        let mut y: Vec<Option<f64>> = (0..24).map(|i| Some(i as f64)).collect();
+        y[12] = None;
+        let fit = cyclic_smoothing_fit(&y, 0.0);
+        for (i, value) in fit.iter().enumerate() {
+            assert!(value.is_finite(), "knot {i} is not finite: {value}");
+        }
+        // The missing knot falls back to its nearest present neighbor.
+        assert!((fit[12] - 11.0).abs() < 1e-6 || (fit[12] - 13.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn periodic_spline_closes_continuously_at_midnight_wrap() {
+        let f: Vec<f64> = (0..24).map(|i| (i as f64 * std::f64::consts::TAU / 24.0).sin()).collect();
+        let curve = periodic_spline_curve(&f, 0.5);
+        let first = curve.first().unwrap();
+        let last = curve.last().unwrap();
+        assert!((first[1] - last[1]).abs() < 1e-6, "wrap discontinuity: {} vs {}", first[1], last[1]);
+    }
+}
+
+#[cfg(test)]
+mod resample_tests {
+    use super::*;
+
+    #[test]
+    fn gap_shorter_than_max_gap_minutes_is_interpolated_even_if_not_a_multiple_of_interval() {
+        // --interval 15 --max-gap 20: a single missing 15-minute slot (15 min)
+        // is shorter than max_gap (20 min) even though 20 isn't a multiple of 15.
+        let values = vec![Some(100.0), None, Some(140.0)];
+        let filled = interpolate_short_gaps(&values, 15, 20);
+        assert_eq!(filled, vec![Some(100.0), Some(120.0), Some(140.0)]);
+    }
+
+    #[test]
+    fn gap_exactly_max_gap_minutes_is_left_null() {
+        let values = vec![Some(100.0), None, Some(140.0)];
+        let filled = interpolate_short_gaps(&values, 15, 15);
+        assert_eq!(filled, vec![Some(100.0), None, Some(140.0)]);
+    }
+
+    #[test]
+    fn gap_longer_than_max_gap_minutes_is_left_null() {
+        let values = vec![Some(100.0), None, None, Some(140.0)];
+        let filled = interpolate_short_gaps(&values, 15, 20);
+        assert_eq!(filled, vec![Some(100.0), None, None, Some(140.0)]);
+    }
+}
+
+#[cfg(test)]
+mod metrics_tests {
+    use super::*;
+
+    #[test]
+    fn compute_metrics_matches_known_mean_and_sd_fixture() {
+        let df = df!(
+            "Glucose Value (mg/dL)" => &[100.0, 120.0, 140.0, 160.0, 80.0],
+        )
+        .unwrap();
+        let thresholds = Thresholds::defaults(GlucoseUnit::MgDl);
+        let metrics = compute_metrics(&df, thresholds, GlucoseUnit::MgDl).unwrap();
+
+        assert!((metrics.mean - 120.0).abs() < 1e-9);
+        assert!((metrics.cv - 26.352313).abs() < 1e-5, "cv: {}", metrics.cv);
+        assert!((metrics.gmi - 6.1804).abs() < 1e-9, "gmi: {}", metrics.gmi);
+        assert!((metrics.time_in_range - 100.0).abs() < 1e-9);
+        assert!((metrics.time_below - 0.0).abs() < 1e-9);
+        assert!((metrics.time_above - 0.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn compute_metrics_counts_out_of_range_readings() {
+        let df = df!(
+            "Glucose Value (mg/dL)" => &[50.0, 90.0, 190.0, 100.0],
+        )
+        .unwrap();
+        let thresholds = Thresholds::defaults(GlucoseUnit::MgDl);
+        let metrics = compute_metrics(&df, thresholds, GlucoseUnit::MgDl).unwrap();
+
+        assert!((metrics.time_below - 25.0).abs() < 1e-9);
+        assert!((metrics.time_above - 25.0).abs() < 1e-9);
+        assert!((metrics.time_in_range - 50.0).abs() < 1e-9);
+    }
+}